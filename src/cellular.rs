@@ -0,0 +1,94 @@
+// Cellular-automata terrain clustering, used to scatter outdoor vegetation
+// and rubble in organic clumps rather than single isolated tiles. Each
+// unobstructed tile is seeded "filled" with `density` probability, then
+// `iterations` rounds of Conway-style smoothing settle the noise into
+// blobs: a tile becomes filled if 5 or more of its 8 neighbors are filled,
+// counting both out-of-bounds tiles and tiles already claimed by roads or
+// buildings as filled, so clusters hug existing obstacles instead of
+// overlapping them. Doors carved by the connectivity pass read as
+// unobstructed (so characters can walk through them), so door tiles are
+// excluded by tile type here, or a cluster could seal an entrance right
+// back up.
+
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::map::{self, MapBuffer};
+use crate::Obstructions;
+
+fn blocked(buffer: &MapBuffer, obstructions: &Obstructions, x: u32, y: u32) -> bool {
+    obstructions.is_obstructed(x, y) || map::is_door(buffer.tile_at(x, y))
+}
+
+pub fn generate_clusters(buffer: &MapBuffer, obstructions: &Obstructions, density: f64, iterations: u32, rng: &mut StdRng) -> Vec<bool> {
+    let w = obstructions.width();
+    let h = obstructions.height();
+    let index = |x: u32, y: u32| (y * w + x) as usize;
+
+    let mut grid = vec![false; (w * h) as usize];
+    for y in 0..h {
+        for x in 0..w {
+            if !blocked(buffer, obstructions, x, y) {
+                grid[index(x, y)] = rng.gen_bool(density);
+            }
+        }
+    }
+
+    for _round in 0..iterations {
+        let mut next = grid.clone();
+        for y in 0..h {
+            for x in 0..w {
+                if blocked(buffer, obstructions, x, y) {
+                    continue;
+                }
+                let mut filled_neighbors = 0;
+                for dy in -1i32..=1 {
+                    for dx in -1i32..=1 {
+                        if dx == 0 && dy == 0 {
+                            continue;
+                        }
+                        let nx = x as i32 + dx;
+                        let ny = y as i32 + dy;
+                        let filled = nx < 0 || ny < 0 || nx >= w as i32 || ny >= h as i32
+                            || blocked(buffer, obstructions, nx as u32, ny as u32)
+                            || grid[index(nx as u32, ny as u32)];
+                        if filled {
+                            filled_neighbors += 1;
+                        }
+                    }
+                }
+                next[index(x, y)] = filled_neighbors >= 5;
+            }
+        }
+        grid = next;
+    }
+
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Obstructions;
+    use rand::SeedableRng;
+
+    #[test]
+    fn clusters_never_cover_obstructed_tiles() {
+        let mut obstructions = Obstructions::new(8, 8);
+        obstructions.obstruct(2, 2, true);
+        let buffer = MapBuffer::new(8, 8);
+        let mut rng = StdRng::seed_from_u64(42);
+        let clusters = generate_clusters(&buffer, &obstructions, 1.0, 3, &mut rng);
+        assert!(!clusters[(2 * 8 + 2) as usize]);
+    }
+
+    #[test]
+    fn clusters_never_cover_door_tiles() {
+        let obstructions = Obstructions::new(8, 8);
+        let mut buffer = MapBuffer::new(8, 8);
+        buffer.set_tile(3, 3, map::DOOR_N);
+        let mut rng = StdRng::seed_from_u64(42);
+        let clusters = generate_clusters(&buffer, &obstructions, 1.0, 3, &mut rng);
+        assert!(!clusters[(3 * 8 + 3) as usize]);
+    }
+}
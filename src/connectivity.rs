@@ -0,0 +1,153 @@
+// Post-generation pass: flood-fills from a random clear starting tile and
+// carves a door into every building that area doesn't touch, then marks
+// that tile as the starting point and the reachable tile farthest from it
+// as the exit (like a dungeon). Road tiles are obstructed, so the road
+// network itself is not part of the flood-filled area, and neither point
+// is necessarily a corner of the map.
+
+use rand::rngs::StdRng;
+
+use crate::map::{self, MapBuffer};
+use crate::{Obstructions, Point, Rectangle};
+
+// Flood-fills unobstructed tiles reachable from `start`, returning a
+// same-size grid of booleans.
+fn flood_fill(obstructions: &Obstructions, start: Point) -> Vec<bool> {
+    let w = obstructions.width();
+    let h = obstructions.height();
+    let mut reachable = vec![false; (w * h) as usize];
+    if obstructions.is_obstructed(start.x, start.y) {
+        return reachable;
+    }
+    let mut stack = vec![start];
+    reachable[(start.y * w + start.x) as usize] = true;
+    while let Some(point) = stack.pop() {
+        let neighbors = [
+            (point.x.wrapping_sub(1), point.y),
+            (point.x + 1, point.y),
+            (point.x, point.y.wrapping_sub(1)),
+            (point.x, point.y + 1),
+        ];
+        for (nx, ny) in neighbors {
+            if nx >= w || ny >= h {
+                continue;
+            }
+            let idx = (ny * w + nx) as usize;
+            if !reachable[idx] && !obstructions.is_obstructed(nx, ny) {
+                reachable[idx] = true;
+                stack.push(Point::new(nx, ny));
+            }
+        }
+    }
+    reachable
+}
+
+// Whether any tile just outside `building`'s footprint is already reachable.
+fn building_is_reachable(building: &Rectangle, reachable: &[bool], w: u32, h: u32) -> bool {
+    let x1 = building.x1.saturating_sub(1);
+    let y1 = building.y1.saturating_sub(1);
+    let x2 = (building.x2 + 1).min(w - 1);
+    let y2 = (building.y2 + 1).min(h - 1);
+    for x in x1..=x2 {
+        for y in y1..=y2 {
+            let inside = x >= building.x1 && x <= building.x2 && y >= building.y1 && y <= building.y2;
+            if !inside && reachable[(y * w + x) as usize] {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn door_tile_for(point: Point, building: &Rectangle) -> usize {
+    if point.x == building.x1 {
+        map::DOOR_W
+    } else if point.x == building.x2 {
+        map::DOOR_E
+    } else if point.y == building.y1 {
+        map::DOOR_N
+    } else {
+        map::DOOR_S
+    }
+}
+
+// Carves a doorway into any building that's cut off from the reachable
+// region, then marks a starting point and the tile farthest from it (the
+// exit) once every building is connected.
+pub fn ensure_connectivity(
+    buffer: &mut MapBuffer,
+    obstructions: &mut Obstructions,
+    buildings: &[Rectangle],
+    rng: &mut StdRng,
+) -> (Point, Point) {
+    let w = obstructions.width();
+    let h = obstructions.height();
+    let seed = obstructions.find_clear_tile(rng);
+    let start = Point::new(seed.0, seed.1);
+    let mut reachable = flood_fill(obstructions, start);
+
+    for building in buildings {
+        if building_is_reachable(building, &reachable, w, h) {
+            continue;
+        }
+        let door_point = building.find_exterior_point(rng);
+        obstructions.obstruct(door_point.x, door_point.y, false);
+        buffer.set_tile(door_point.x, door_point.y, door_tile_for(door_point, building));
+        reachable = flood_fill(obstructions, start);
+    }
+
+    let mut exit = start;
+    let mut exit_distance = -1i64;
+    for x in 0..w {
+        for y in 0..h {
+            if reachable[(y * w + x) as usize] {
+                let dx = x as i64 - start.x as i64;
+                let dy = y as i64 - start.y as i64;
+                let distance = dx * dx + dy * dy;
+                if distance > exit_distance {
+                    exit_distance = distance;
+                    exit = Point::new(x, y);
+                }
+            }
+        }
+    }
+
+    (start, exit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn carves_a_door_for_an_unreachable_building() {
+        let mut buffer = MapBuffer::new(10, 10);
+        let mut obstructions = Obstructions::new(10, 10);
+        let building = Rectangle { x1: 3, y1: 3, x2: 6, y2: 6 };
+        for y in building.y1..=building.y2 {
+            for x in building.x1..=building.x2 {
+                let on_edge = x == building.x1 || x == building.x2 || y == building.y1 || y == building.y2;
+                buffer.set_tile(x, y, if on_edge { map::WALL_N } else { map::FLOOR });
+                obstructions.obstruct(x, y, on_edge);
+            }
+        }
+        // cut the building off: obstruct the ring of tiles just outside it,
+        // so no exterior tile next to it is reachable from any start
+        for y in building.y1 - 1..=building.y2 + 1 {
+            for x in building.x1 - 1..=building.x2 + 1 {
+                let inside = x >= building.x1 && x <= building.x2 && y >= building.y1 && y <= building.y2;
+                if !inside {
+                    obstructions.obstruct(x, y, true);
+                }
+            }
+        }
+        let mut rng = StdRng::seed_from_u64(3);
+        ensure_connectivity(&mut buffer, &mut obstructions, &[building], &mut rng);
+
+        let has_door = (building.x1..=building.x2)
+            .flat_map(|x| (building.y1..=building.y2).map(move |y| (x, y)))
+            .any(|(x, y)| map::is_door(buffer.tile_at(x, y)));
+        assert!(has_door, "expected a door to be carved into the unreachable building");
+    }
+}
@@ -1,11 +1,24 @@
 use image::{DynamicImage, RgbaImage};
 use image::io::Reader;
-use rand::{thread_rng, Rng};
-use rand::rngs::ThreadRng;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 use clap::Parser;
 use std::cmp::{min, max};
 use std::env;
 
+mod wfc;
+mod modifiers;
+mod svg;
+mod connectivity;
+mod map;
+mod render;
+mod maze;
+mod cellular;
+mod text;
+
+use map::MapBuffer;
+use modifiers::{Geometry, MapModifier};
+
 const WEB_MODE_VAR: &str = "BATTLEMAPPER_WEB";
 const WEB_QUERY_VAR: &str = "QUERY_STRING";
 const TILE_SIZE: u32 = 32;
@@ -29,7 +42,64 @@ struct Args {
     building_count: u8,
 
     #[clap(short = 'B', long, default_value_t = 16)]
-    building_size: u8
+    building_size: u8,
+
+    #[clap(long, default_value_t = true)]
+    terrain: bool,
+
+    #[clap(long, default_value_t = true)]
+    roads: bool,
+
+    #[clap(long, default_value_t = true)]
+    buildings: bool,
+
+    #[clap(long, value_enum, default_value = "none")]
+    symmetry: Symmetry,
+
+    #[clap(long, default_value_t = false)]
+    los: bool,
+
+    #[clap(long, value_enum, default_value = "png")]
+    format: OutputFormat,
+
+    #[clap(long, default_value_t = false)]
+    curved_roads: bool,
+
+    #[clap(long)]
+    seed: Option<u64>,
+
+    #[clap(long, value_enum, default_value = "rooms")]
+    building_style: BuildingStyle,
+
+    #[clap(long, default_value_t = 0.45)]
+    obstacle_density: f64,
+
+    #[clap(long, default_value_t = 4)]
+    obstacle_iterations: u8,
+
+    #[clap(long, default_value_t = 3)]
+    large_obstacle_count: u8
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq)]
+enum Symmetry {
+    None,
+    Horizontal,
+    Vertical,
+    Both
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq)]
+enum OutputFormat {
+    Png,
+    Svg,
+    Text
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq)]
+enum BuildingStyle {
+    Rooms,
+    Maze
 }
 
 #[derive(Copy, Clone, PartialEq)]
@@ -38,7 +108,7 @@ enum Orientation {
     Vert
 }
 
-#[derive(PartialEq)]
+#[derive(Copy, Clone, PartialEq)]
 struct Point {
     x: u32,
     y: u32
@@ -50,6 +120,7 @@ impl Point {
     }
 }
 
+#[derive(Copy, Clone)]
 struct Line {
     x: u32,
     y: u32,
@@ -58,7 +129,7 @@ struct Line {
 }
 
 impl Line {
-    fn find_point_within(&self, margin: u32, rng: &mut ThreadRng) -> Point {
+    fn find_point_within(&self, margin: u32, rng: &mut StdRng) -> Point {
         let mut point = Point::new(self.x, self.y);
         let distance = rng.gen_range(margin..self.length-margin);
         match self.orientation {
@@ -133,7 +204,7 @@ impl Rectangle {
         self.width() >= min_size * 2 || self.height() >= min_size * 2
     }
 
-    fn divide_with_lines(&self, line_count: u32, line_margin: u32, rng: &mut ThreadRng) -> Vec<Line> {
+    fn divide_with_lines(&self, line_count: u32, line_margin: u32, rng: &mut StdRng) -> Vec<Line> {
         let mut lines: Vec<Line> = Vec::new();
         for _r in 0..line_count {
             let mut line = Line{x: self.x1, y: self.y1, length: 0, orientation: Orientation::Horiz};
@@ -265,7 +336,7 @@ impl Rectangle {
         lines
     }
 
-    fn randomly_divide(&self, min_size: u32, rng: &mut ThreadRng) -> (Rectangle, Rectangle) {
+    fn randomly_divide(&self, min_size: u32, rng: &mut StdRng) -> (Rectangle, Rectangle) {
         let mut rect1 = *self;
         let mut rect2 = *self;
         let mut division_line = Orientation::Horiz;
@@ -341,11 +412,11 @@ impl Rectangle {
         border
     }
 
-    fn find_point_within(&self, margin: u32, rng: &mut ThreadRng) -> Point {
+    fn find_point_within(&self, margin: u32, rng: &mut StdRng) -> Point {
         Point::new(rng.gen_range(self.x1+margin..self.x2-margin+1), rng.gen_range(self.y1+margin..self.y2-margin+1))
     }
 
-    fn find_exterior_point(&self, rng: &mut ThreadRng) -> Point {
+    fn find_exterior_point(&self, rng: &mut StdRng) -> Point {
         let mut point = Point::new(self.x1, self.y1);
         let horiz_wall: bool = rng.gen();
         let lowest: bool = rng.gen();
@@ -374,24 +445,18 @@ impl Rectangle {
 struct Obstructions {
     w: u32,
     h: u32,
-    tiles: Vec<bool>,
-    unobstructed_count: u32
+    tiles: Vec<bool>
 }
 
 impl Obstructions {
     fn new(width: u32, height: u32) -> Obstructions {
         let mut t: Vec<bool> = Vec::new();
         t.resize((width * height) as usize, false);
-        Obstructions {w: width, h: height, tiles: t, unobstructed_count: 0}
+        Obstructions {w: width, h: height, tiles: t}
     }
 
     fn obstruct(&mut self, x: u32, y: u32, obstructed: bool) {
         let t = (y * self.w + x) as usize;
-        if obstructed && !self.tiles[t] {
-            self.unobstructed_count += 1;
-        } else if !obstructed && self.tiles[t] {
-            self.unobstructed_count -= 1;
-        }
         self.tiles[t] = obstructed;
     }
 
@@ -412,11 +477,23 @@ impl Obstructions {
         obstructed
     }
 
-    fn get_unobstructed_count(&self) -> u32 {
-        self.unobstructed_count
+    fn obstruct_rectangle(&mut self, r: &Rectangle, obstructed: bool) {
+        for x in r.x1..r.x2+1 {
+            for y in r.y1..r.y2+1 {
+                self.obstruct(x, y, obstructed);
+            }
+        }
+    }
+
+    fn width(&self) -> u32 {
+        self.w
+    }
+
+    fn height(&self) -> u32 {
+        self.h
     }
 
-    fn find_clear_tile(&self, rng: &mut ThreadRng) -> (u32, u32) {
+    fn find_clear_tile(&self, rng: &mut StdRng) -> (u32, u32) {
         let mut choosing = true;
         let mut x = 0;
         let mut y = 0;
@@ -431,8 +508,80 @@ impl Obstructions {
         (x, y)
     }
 
-    fn find_clear_rectangle(&self, min_size: u32, max_size: u32, rng: &mut ThreadRng) -> Rectangle {
+    // Like `find_clear_tile`, but for a multi-tile footprint: keeps
+    // re-rolling an origin until the whole `size` rectangle anchored there
+    // is unobstructed, so a large obstacle never lands on a road, wall or
+    // door. Doors carved by the connectivity pass read as unobstructed (so
+    // characters can pass through them), so doors are checked separately
+    // by tile type against `buffer`.
+    fn find_clear_footprint(&self, buffer: &MapBuffer, size: map::TileSize, rng: &mut StdRng) -> (u32, u32) {
+        let max_x = (self.w.saturating_sub(size.w) + 1).max(1);
+        let max_y = (self.h.saturating_sub(size.h) + 1).max(1);
+        let mut choosing = true;
+        let mut x = 0;
+        let mut y = 0;
+        while choosing {
+            choosing = false;
+            x = rng.gen_range(0..max_x);
+            y = rng.gen_range(0..max_y);
+            let footprint = Rectangle { x1: x, y1: y, x2: x + size.w - 1, y2: y + size.h - 1 };
+            if self.obstructed_rectangle(&footprint) || footprint_has_door(buffer, &footprint) {
+                choosing = true;
+            }
+        }
+        (x, y)
+    }
+
+    // Supercover line walk from `a` to `b`: visits every grid cell the ideal
+    // line passes through, not just the thin Bresenham line, so sightlines
+    // can't slip between two diagonally-adjacent walls.
+    fn line_of_sight(&self, a: Point, b: Point) -> bool {
+        if self.is_obstructed(a.x, a.y) {
+            return false;
+        }
+        let dx = (b.x as i64 - a.x as i64).abs();
+        let dy = (b.y as i64 - a.y as i64).abs();
+        let sign_x: i64 = if b.x >= a.x { 1 } else { -1 };
+        let sign_y: i64 = if b.y >= a.y { 1 } else { -1 };
+        let mut x = a.x as i64;
+        let mut y = a.y as i64;
+        let mut ix: i64 = 0;
+        let mut iy: i64 = 0;
+
+        while ix < dx || iy < dy {
+            let decision = (1 + 2 * ix) * dy - (1 + 2 * iy) * dx;
+            if decision == 0 {
+                // The line crosses a grid corner; both flanking cells count
+                // as blocking so a wall can't be skirted diagonally.
+                let flank_a = (x + sign_x, y);
+                let flank_b = (x, y + sign_y);
+                if self.is_obstructed(flank_a.0 as u32, flank_a.1 as u32) || self.is_obstructed(flank_b.0 as u32, flank_b.1 as u32) {
+                    return false;
+                }
+                x += sign_x;
+                y += sign_y;
+                ix += 1;
+                iy += 1;
+            } else if decision < 0 {
+                x += sign_x;
+                ix += 1;
+            } else {
+                y += sign_y;
+                iy += 1;
+            }
+            if self.is_obstructed(x as u32, y as u32) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn find_clear_rectangle(&self, min_size: u32, max_size: u32, rng: &mut StdRng) -> Rectangle {
         let outer_bounds = Rectangle{x1: 0, y1: 0, x2: self.w - 1, y2: self.h -1};
+        self.find_clear_rectangle_within(&outer_bounds, min_size, max_size, rng)
+    }
+
+    fn find_clear_rectangle_within(&self, outer_bounds: &Rectangle, min_size: u32, max_size: u32, rng: &mut StdRng) -> Rectangle {
         //let mut rectangle = outer_bounds;
         let mut ok = false;
         let mut size_x = min_size;
@@ -452,7 +601,7 @@ impl Obstructions {
                 while growing_x || growing_y {
                     //println!("looping");
                     if growing_x {
-                        if point.x > size_x + 2 && point.x + size_x < self.w - 3 && size_x < max_size / 2 {
+                        if point.x > size_x + 2 && point.x + size_x < outer_bounds.x2 - 2 && size_x < max_size / 2 {
                             size_x += 1;
                             if self.obstructed_rectangle(&Rectangle {x1: point.x - size_x - 1, y1: point.y - size_y - 1, x2: point.x + size_x + 1, y2: point.y + size_y + 1 }) {
                                 growing_x = false;
@@ -462,7 +611,7 @@ impl Obstructions {
                         }
                     }
                     if growing_y {
-                        if point.y > size_y + 2 && point.y + size_y < self.h - 3 && size_y < max_size / 2 {
+                        if point.y > size_y + 2 && point.y + size_y < outer_bounds.y2 - 2 && size_y < max_size / 2 {
                             size_y += 1;
                             if self.obstructed_rectangle(&Rectangle {x1: point.x - size_x - 1, y1: point.y - size_y - 1, x2: point.x + size_x + 1, y2: point.y + size_y + 1 }) {
                                 growing_y = false;
@@ -476,7 +625,18 @@ impl Obstructions {
         }
         //println!("clear rectangle point {} {} size {} {}", point.x, point.y, size_x, size_y);
         Rectangle {x1: point.x - size_x, y1: point.y - size_y, x2: point.x + size_x, y2: point.y + size_y }
-    }    
+    }
+}
+
+fn footprint_has_door(buffer: &MapBuffer, r: &Rectangle) -> bool {
+    for x in r.x1..r.x2 + 1 {
+        for y in r.y1..r.y2 + 1 {
+            if map::is_door(buffer.tile_at(x, y)) {
+                return true;
+            }
+        }
+    }
+    false
 }
 
 fn opposite_orientation(original: Orientation) -> Orientation {
@@ -486,6 +646,19 @@ fn opposite_orientation(original: Orientation) -> Orientation {
     }
 }
 
+// Alpha-blends a translucent color over one tile, used by the LOS overlay.
+fn tint_tile(img: &mut RgbaImage, x: u32, y: u32, color: image::Rgba<u8>) {
+    let alpha = color.0[3] as u32;
+    for px in x * TILE_SIZE..(x + 1) * TILE_SIZE {
+        for py in y * TILE_SIZE..(y + 1) * TILE_SIZE {
+            let pixel = img.get_pixel_mut(px, py);
+            for c in 0..3 {
+                pixel.0[c] = ((pixel.0[c] as u32 * (255 - alpha) + color.0[c] as u32 * alpha) / 255) as u8;
+            }
+        }
+    }
+}
+
 struct BattleMap {
     w: u32,
     h: u32,
@@ -493,19 +666,51 @@ struct BattleMap {
     road_width: u32,
     building_count: u32,
     building_size: u32,
-    img: RgbaImage
+    terrain: bool,
+    roads: bool,
+    buildings: bool,
+    symmetry: Symmetry,
+    los: bool,
+    curved_roads: bool,
+    building_style: BuildingStyle,
+    obstacle_density: f64,
+    obstacle_iterations: u32,
+    large_obstacle_count: u32,
+    seed: Option<u64>,
+    effective_seed: u64,
+    img: RgbaImage,
+    geometry: Vec<Geometry>,
+    buffer: Option<MapBuffer>,
+    starting_point: Option<Point>,
+    exit_point: Option<Point>
 }
 
 impl BattleMap {
-    fn new(w: u32, h: u32, road_count: u32, road_width: u32, building_count: u32, building_size: u32 ) -> BattleMap {        
+    fn new(w: u32, h: u32, road_count: u32, road_width: u32, building_count: u32, building_size: u32, terrain: bool, roads: bool, buildings: bool, symmetry: Symmetry, los: bool, curved_roads: bool, building_style: BuildingStyle, obstacle_density: f64, obstacle_iterations: u32, large_obstacle_count: u32, seed: Option<u64>) -> BattleMap {
         BattleMap {
-            w, 
-            h, 
-            road_count, 
-            road_width, 
-            building_count, 
+            w,
+            h,
+            road_count,
+            road_width,
+            building_count,
             building_size,
-            img: RgbaImage::new(w * TILE_SIZE, h * TILE_SIZE)
+            terrain,
+            roads,
+            buildings,
+            symmetry,
+            los,
+            curved_roads,
+            building_style,
+            obstacle_density,
+            obstacle_iterations,
+            large_obstacle_count,
+            seed,
+            effective_seed: 0,
+            img: RgbaImage::new(w * TILE_SIZE, h * TILE_SIZE),
+            geometry: Vec::new(),
+            buffer: None,
+            starting_point: None,
+            exit_point: None
         }
     }
 
@@ -513,13 +718,10 @@ impl BattleMap {
         (self.w * TILE_SIZE, self.h * TILE_SIZE)
     }
 
-    fn road_margin(&self) -> u32 {
-        self.road_width / 2 + 1
-    }
-
     fn generate(&mut self) {
-        let mut bytes: Vec<u8> = Vec::new();
-        let mut rng = thread_rng();
+        self.effective_seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        eprintln!("seed {}", self.effective_seed);
+        let mut rng = StdRng::seed_from_u64(self.effective_seed);
         let dim = self.pixel_dimensions();
         self.img = RgbaImage::new(dim.0, dim.1);
 
@@ -527,219 +729,177 @@ impl BattleMap {
             Ok(raw_tiles) => raw_tiles,
             Err(_e) => return
         };
-        let mut tiles = match raw_tiles.decode() {
+        let tiles = match raw_tiles.decode() {
             Ok(tiles) => tiles,
             Err(_e) => return
         };
-    
+
         let mut obstructions = Obstructions::new(self.w, self.h);
-    
-        // dirt
-    
-        let dirt_tile = image::imageops::crop(&mut tiles, 0, 0, TILE_SIZE, TILE_SIZE);
-    
-        for x in 0..self.w {
-            for y in 0..self.h {
-                image::imageops::overlay(&mut self.img, &dirt_tile, x * TILE_SIZE, y * TILE_SIZE);
-            }
+        let mut buffer = MapBuffer::new(self.w, self.h);
+
+        // Symmetric maps are only generated in the half/quadrant nearest the
+        // origin, then mirrored across the center once the pipeline is done.
+        let gen_w = if matches!(self.symmetry, Symmetry::Vertical | Symmetry::Both) { (self.w + 1) / 2 } else { self.w };
+        let gen_h = if matches!(self.symmetry, Symmetry::Horizontal | Symmetry::Both) { (self.h + 1) / 2 } else { self.h };
+        let bounds = Rectangle { x1: 0, y1: 0, x2: gen_w - 1, y2: gen_h - 1 };
+
+        let mut pipeline: Vec<Box<dyn MapModifier>> = Vec::new();
+        if self.terrain {
+            pipeline.push(Box::new(modifiers::TerrainModifier));
         }
-    
-        // roads
-    
-        let full_rect = Rectangle{ x1: 0, y1: 0, x2: self.w - 1, y2: self.h - 1 };
-        let roads = full_rect.divide_with_lines(self.road_count, self.road_margin(), &mut rng);
-    
-        let dirt_tile = tiles.crop_imm(32, 0, TILE_SIZE, TILE_SIZE);
-        let car_h_tile = tiles.crop_imm(64, 32, 64, 32);
-        let car_v_tile = tiles.crop_imm(128, 0, 32, 64);
-    
-        for road in &roads {
-            let mut x = road.x;
-            let mut y = road.y;
-            //println!("road origin {} {} {}", x, y, road.length);
-            for _t in 0..road.length {
-                obstructions.obstruct(x, y, true);
-                match road.orientation {
-                    Orientation::Horiz => {
-                        for w in 0..self.road_width {
-                            //println!("overlay {} {} {}", x, y, w);
-                            image::imageops::overlay(&mut self.img, &dirt_tile, x * TILE_SIZE, (y - (self.road_width / 2) + w) * TILE_SIZE);
-                        }
-                        for w in 0..self.road_margin() {
-                            obstructions.obstruct(x, y - w, true);
-                            obstructions.obstruct(x, y + w, true);
-                        }
-                        x += 1;
-                    },
-                    Orientation::Vert => {
-                        for w in 0..self.road_width {
-                            image::imageops::overlay(&mut self.img, &dirt_tile, (x - (self.road_width / 2) + w) * TILE_SIZE, y * TILE_SIZE);
-                        }
-                        for w in 0..self.road_margin() {
-                            obstructions.obstruct(x - w, y, true);
-                            obstructions.obstruct(x + w, y, true);
-                        }
-                        y += 1;
+        if self.roads {
+            pipeline.push(Box::new(modifiers::RoadModifier { count: self.road_count, width: self.road_width, curved: self.curved_roads }));
+        }
+        if self.buildings {
+            pipeline.push(Box::new(modifiers::BuildingModifier { count: self.building_count, max_size: self.building_size, style: self.building_style }));
+        }
+        self.geometry.clear();
+        self.starting_point = None;
+        self.exit_point = None;
+        for modifier in &pipeline {
+            self.geometry.push(modifier.apply(&mut buffer, &mut obstructions, &bounds, &mut rng));
+        }
+
+        if self.symmetry != Symmetry::None {
+            modifiers::mirror_map(&mut buffer, &mut obstructions, self.w, self.h, self.symmetry);
+
+            // `mirror_map` only mirrors the map buffer's tiles and the
+            // obstruction grid; the geometry list (consumed by SVG export and the car
+            // overlay below) and `MapBuffer`'s own road trace need the same
+            // treatment or they keep describing just the generated
+            // half/quadrant. Buildings are mirrored separately below, once
+            // against the full generated list, so skip that variant here.
+            buffer.roads = modifiers::mirror_point_paths(&buffer.roads, self.w, self.h, self.symmetry);
+            for layer in self.geometry.iter_mut() {
+                match layer {
+                    Geometry::Terrain(cells) => *cells = modifiers::mirror_terrain(cells, self.w, self.h, self.symmetry),
+                    Geometry::Roads(roads, curves) => {
+                        *roads = modifiers::mirror_lines(roads, self.w, self.h, self.symmetry);
+                        *curves = modifiers::mirror_point_paths(curves, self.w, self.h, self.symmetry);
                     }
+                    Geometry::Buildings(_) => {}
                 }
             }
         }
-    
+
+        // make every building reachable from a random clear starting tile
+        // (road tiles are obstructed and not part of that area), and mark
+        // that tile as the starting point and the reachable tile farthest
+        // from it as the exit; neither is necessarily a map corner.
+        // Run after mirroring, against the full building list (mirrored
+        // copies included), so a building in a mirrored quadrant can still
+        // get a door carved instead of staying sealed.
+        let generated_buildings: Vec<map::Building> = self.geometry.iter().filter_map(|g| match g {
+            Geometry::Buildings(b) => Some(b.clone()),
+            _ => None
+        }).flatten().collect();
+        let buildings = modifiers::mirror_buildings(&generated_buildings, self.w, self.h, self.symmetry);
+        for layer in self.geometry.iter_mut() {
+            if let Geometry::Buildings(b) = layer {
+                *b = buildings.clone();
+            }
+        }
+        buffer.buildings = buildings.clone();
+        if self.buildings {
+            let building_rects: Vec<Rectangle> = buildings.iter().map(|b| b.rect).collect();
+            let (start, exit) = connectivity::ensure_connectivity(&mut buffer, &mut obstructions, &building_rects, &mut rng);
+            self.starting_point = Some(start);
+            self.exit_point = Some(exit);
+        }
+
+        // outdoor obstacles: cellular automata settles seeded noise into
+        // organic clusters of vegetation and rubble, leaving clearings
+        // between them instead of single scattered tiles
+
+        let clusters = cellular::generate_clusters(&buffer, &obstructions, self.obstacle_density, self.obstacle_iterations, &mut rng);
+        for y in 0..self.h {
+            for x in 0..self.w {
+                if clusters[(y * self.w + x) as usize] {
+                    obstructions.obstruct(x, y, true);
+                    let tile = if rng.gen::<bool>() { map::BUSH } else { map::RUBBLE };
+                    buffer.set_tile(x, y, tile);
+                }
+            }
+        }
+
+        // large obstacles: wagons and boulder piles that occupy more than a
+        // single tile, so the whole footprint (not just its origin) has to
+        // clear roads, walls and doors before it's stamped in
+        const LARGE_OBSTACLES: [(map::TileSize, usize); 2] = [
+            (map::TileSize { w: 2, h: 2 }, map::CRATE), // wagon
+            (map::TileSize { w: 1, h: 3 }, map::RUBBLE), // boulder pile
+        ];
+        for _o in 0..self.large_obstacle_count {
+            let (size, tile) = LARGE_OBSTACLES[rng.gen_range(0..LARGE_OBSTACLES.len())];
+            let (x, y) = obstructions.find_clear_footprint(&buffer, size, &mut rng);
+            let footprint = Rectangle { x1: x, y1: y, x2: x + size.w - 1, y2: y + size.h - 1 };
+            obstructions.obstruct_rectangle(&footprint, true);
+            buffer.fill_footprint(x, y, size, tile);
+        }
+
+        // the obstruction grid is the single source of truth for what a
+        // character can stand on; copy its final state (terrain, roads,
+        // buildings, carved doors, and outdoor obstacles all included by
+        // this point) into the map model so a consumer of `MapBuffer` alone
+        // doesn't need `Obstructions` to know what's walkable
+        for y in 0..self.h {
+            for x in 0..self.w {
+                buffer.set_walkable(x, y, !obstructions.is_obstructed(x, y));
+            }
+        }
+
+        // render the populated buffer to pixels; everything past this point
+        // (cars, LOS tint, grid lines) is a cosmetic pass over the raster
+        // image rather than part of the map model.
+        self.img = render::render(&buffer, &tiles);
+        self.buffer = Some(buffer);
+
+        let roads: Vec<Line> = self.geometry.iter().filter_map(|g| match g {
+            Geometry::Roads(r, _) => Some(r.clone()),
+            _ => None
+        }).flatten().collect();
+        let car_h_tile = tiles.crop_imm(64, 32, 64, 32);
+        let car_v_tile = tiles.crop_imm(128, 0, 32, 64);
         for road in &roads {
             if road.length > 4 {
                 let car = road.find_point_within(2, &mut rng);
                 if rng.gen::<bool>() {
-                    image::imageops::overlay(&mut self.img, &car_v_tile, car.x * TILE_SIZE, car.y * TILE_SIZE);    
+                    image::imageops::overlay(&mut self.img, &car_v_tile, car.x * TILE_SIZE, car.y * TILE_SIZE);
                 } else {
-                    image::imageops::overlay(&mut self.img, &car_h_tile, car.x * TILE_SIZE, car.y * TILE_SIZE);    
+                    image::imageops::overlay(&mut self.img, &car_h_tile, car.x * TILE_SIZE, car.y * TILE_SIZE);
                 }
-                
             }
         }
-    
-        // buildings
-    
-        //println!("start buildings");
-    
-        let floor_tile = tiles.crop_imm(96, 0, TILE_SIZE, TILE_SIZE);
-        let wall_nw_tile = tiles.crop_imm(0, 96, TILE_SIZE, TILE_SIZE);
-        let wall_ne_tile = tiles.crop_imm(32, 96, TILE_SIZE, TILE_SIZE);
-        let wall_sw_tile = tiles.crop_imm(64, 96, TILE_SIZE, TILE_SIZE);
-        let wall_se_tile = tiles.crop_imm(96, 96, TILE_SIZE, TILE_SIZE);
-        let wall_n_tile = tiles.crop_imm(128, 96, TILE_SIZE, TILE_SIZE);
-        let wall_s_tile = tiles.crop_imm(160, 96, TILE_SIZE, TILE_SIZE);
-        let wall_w_tile = tiles.crop_imm(192, 96, TILE_SIZE, TILE_SIZE);
-        let wall_e_tile = tiles.crop_imm(224, 96, TILE_SIZE, TILE_SIZE);
-        let door_w_tile = tiles.crop_imm(0, 64, TILE_SIZE, TILE_SIZE);
-        let door_n_tile = tiles.crop_imm(32, 64, TILE_SIZE, TILE_SIZE);
-        let door_e_tile = tiles.crop_imm(64, 64, TILE_SIZE, TILE_SIZE);
-        let door_s_tile = tiles.crop_imm(96, 64, TILE_SIZE, TILE_SIZE);
-        let crate_tile = tiles.crop_imm(0, 32, TILE_SIZE, TILE_SIZE);
-    
-        for b in 0..self.building_count {
-            //println!("building {}", b);
-            let mut building = obstructions.find_clear_rectangle(3, self.building_size, &mut rng);
-            building.shrink(1);
-            let door_count = building.perimeter() / 20 + 1;
-            let mut doors = Vec::new();
-            for _d in 0..door_count {
-                doors.push(building.find_exterior_point(&mut rng));
-            }
-            for x in building.x1..building.x2+1 {
-                for y in building.y1..building.y2+1 {
-                    image::imageops::overlay(&mut self.img, &floor_tile, x * TILE_SIZE, y * TILE_SIZE);
-                    let point = Point::new(x, y);
-                    if doors.contains(&point) {
-                        if x == building.x1 {
-                            image::imageops::overlay(&mut self.img, &door_w_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        } else if x == building.x2 {
-                            image::imageops::overlay(&mut self.img, &door_e_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        } else if y == building.y1 {
-                            image::imageops::overlay(&mut self.img, &door_n_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        } else {
-                            image::imageops::overlay(&mut self.img, &door_s_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        }
-                    } else if x == building.x1 {
-                        if y == building.y1 {
-                            image::imageops::overlay(&mut self.img, &wall_nw_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        } else if y == building.y2 {
-                            image::imageops::overlay(&mut self.img, &wall_sw_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        } else {
-                            image::imageops::overlay(&mut self.img, &wall_w_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        }
-                    } else if x == building.x2 {
-                        if y == building.y1 {
-                            image::imageops::overlay(&mut self.img, &wall_ne_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        } else if y == building.y2 {
-                            image::imageops::overlay(&mut self.img, &wall_se_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        } else {
-                            image::imageops::overlay(&mut self.img, &wall_e_tile, x * TILE_SIZE, y * TILE_SIZE);
-                        }
-                    } else if y == building.y1 {
-                        image::imageops::overlay(&mut self.img, &wall_n_tile, x * TILE_SIZE, y * TILE_SIZE);
-                    } else if y == building.y2 {
-                        image::imageops::overlay(&mut self.img, &wall_s_tile, x * TILE_SIZE, y * TILE_SIZE);
-                    }    
-                    obstructions.obstruct(x, y, true);
-                }
-            }
-    
-    
-            //let mut walls = Vec::new();
-            let wall_count = building.area() / 30;
-            let walls = building.divide_with_lines(wall_count, 3, &mut rng);
-            for wall in &walls {
-                let mut draw_length = wall.length;
-                match wall.orientation {
-                    Orientation::Horiz => {
-                        if wall.x > building.x1 && wall.x + wall.length <= building.x2 {
-                            draw_length -= 1;
-                        }
-                    },
-                    Orientation::Vert => {
-                        if wall.y > building.y1 && wall.y + wall.length <= building.y2 {
-                            draw_length -= 1;
-                        }
-                    }
-                }
-                let mut door = 1;
-                if draw_length > 3 {
-                    door = rng.gen_range(1..draw_length-2);
-                }
-                for l in 0..draw_length {
-                    if l == door {
-                        match wall.orientation {
-                            Orientation::Horiz => {
-                                image::imageops::overlay(&mut self.img, &door_n_tile, (wall.x + l) * TILE_SIZE, wall.y * TILE_SIZE);
-                            },
-                            Orientation::Vert => {
-                                image::imageops::overlay(&mut self.img, &door_w_tile, wall.x * TILE_SIZE, (wall.y + l) * TILE_SIZE);
-                            }
-                        }
+
+        if let Some(start) = self.starting_point {
+            tint_tile(&mut self.img, start.x, start.y, image::Rgba([0, 80, 255, 140]));
+        }
+        if let Some(exit) = self.exit_point {
+            tint_tile(&mut self.img, exit.x, exit.y, image::Rgba([255, 200, 0, 140]));
+        }
+
+        // line-of-sight overlay: tint every tile visible from a random
+        // vantage point green, every blocked tile red, so a GM can
+        // pre-compute cover from building walls and cars.
+
+        if self.los {
+            let vantage = obstructions.find_clear_tile(&mut rng);
+            let vantage_point = Point::new(vantage.0, vantage.1);
+            for x in 0..self.w {
+                for y in 0..self.h {
+                    let visible = obstructions.line_of_sight(vantage_point, Point::new(x, y));
+                    let color = if visible {
+                        image::Rgba([0, 255, 0, 80])
                     } else {
-                        match wall.orientation {
-                            Orientation::Horiz => {
-                                image::imageops::overlay(&mut self.img, &wall_n_tile, (wall.x + l) * TILE_SIZE, wall.y * TILE_SIZE);
-                            },
-                            Orientation::Vert => {
-                                image::imageops::overlay(&mut self.img, &wall_w_tile, wall.x * TILE_SIZE, (wall.y + l) * TILE_SIZE);
-                            }
-                        }    
-                    }
-                }
-            }
-            
-            let obstacles = building.area() / 50;
-            for _o in 0..obstacles {
-                let mut thing = Point::new(0, 0);
-                let mut finding = true;
-                while finding {
-                    finding = false;
-                    thing = building.find_point_within(1, &mut rng);
-                    for wall in &walls {
-                        if wall.point_intersects(&thing) {
-                            finding = true;
-                        }
-                    }
+                        image::Rgba([255, 0, 0, 80])
+                    };
+                    tint_tile(&mut self.img, x, y, color);
                 }
-                image::imageops::overlay(&mut self.img, &crate_tile, thing.x * TILE_SIZE, thing.y * TILE_SIZE);
             }
         }
-    
-        // outdoor obstacles
-    
-        //println!("start obstacles");
-    
-        let bush_tile = tiles.crop_imm(32, 32, TILE_SIZE, TILE_SIZE);
-        let obstacles = obstructions.get_unobstructed_count() / 50;
-        for _o in 0..obstacles {
-            let coords = obstructions.find_clear_tile(&mut rng);
-            obstructions.obstruct(coords.0, coords.1, true);
-            image::imageops::overlay(&mut self.img, &bush_tile, coords.0 * TILE_SIZE, coords.1 * TILE_SIZE);
-        }
-    
+
         // grid
-    
+
         for x in 0..self.img.width() {
             for y in 0..self.img.height() {
                 if x % TILE_SIZE == 0 || y % TILE_SIZE == 0 {
@@ -776,6 +936,17 @@ impl BattleMap {
     fn save_to(&self, filename: &str) {
         self.img.save(filename);
     }
+
+    fn svg(&self) -> String {
+        svg::render(self.w, self.h, self.road_width, &self.geometry)
+    }
+
+    fn text(&self) -> String {
+        match &self.buffer {
+            Some(buffer) => text::render(buffer),
+            None => String::new()
+        }
+    }
 }
 
 // main program function
@@ -811,21 +982,77 @@ fn main() {
         args.road_count as u32,
         args.road_width as u32,
         args.building_count as u32,
-        args.building_size as u32
+        args.building_size as u32,
+        args.terrain,
+        args.roads,
+        args.buildings,
+        args.symmetry,
+        args.los,
+        args.curved_roads,
+        args.building_style,
+        args.obstacle_density,
+        args.obstacle_iterations as u32,
+        args.large_obstacle_count as u32,
+        args.seed
     );
 
     eprintln!("{} {}", map.w, map.h);
 
     map.generate();
 
-    if web_mode {
-        let img_b64 = map.base64();
-        println!("Content-type: text/plain\n");
-        println!("{}", img_b64);    
-    } else {
-        map.save_to("map.png");
+    match args.format {
+        OutputFormat::Svg => {
+            let svg_doc = map.svg();
+            if web_mode {
+                println!("Content-type: image/svg+xml");
+                println!("X-Seed: {}\n", map.effective_seed);
+                println!("{}", svg_doc);
+            } else {
+                std::fs::write("map.svg", svg_doc).expect("failed to write map.svg");
+            }
+        },
+        OutputFormat::Png => {
+            if web_mode {
+                let img_b64 = map.base64();
+                println!("Content-type: text/plain");
+                println!("X-Seed: {}\n", map.effective_seed);
+                println!("{}", img_b64);
+            } else {
+                map.save_to("map.png");
+            }
+        },
+        OutputFormat::Text => {
+            let text_doc = map.text();
+            if web_mode {
+                println!("Content-type: text/plain");
+                println!("X-Seed: {}\n", map.effective_seed);
+                println!("{}", text_doc);
+            } else {
+                std::fs::write("map.txt", text_doc).expect("failed to write map.txt");
+            }
+        }
     }
 
     //println!("<html><body><p>Hello world</p><img src=\"data:image/png;base64,{}\"></body></html>", img_b64);
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_of_sight_blocked_by_diagonal_corner() {
+        let mut obstructions = Obstructions::new(5, 5);
+        obstructions.obstruct(2, 1, true);
+        obstructions.obstruct(1, 2, true);
+        let blocked = !obstructions.line_of_sight(Point::new(0, 0), Point::new(3, 3));
+        assert!(blocked, "two diagonally touching walls should block a sightline crossing their shared corner");
+    }
+
+    #[test]
+    fn line_of_sight_clear_path() {
+        let obstructions = Obstructions::new(5, 5);
+        assert!(obstructions.line_of_sight(Point::new(0, 0), Point::new(4, 4)));
+    }
+}
+
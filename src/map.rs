@@ -0,0 +1,192 @@
+// Abstract map model, decoupled from rendering. Generation populates a
+// `MapBuffer` describing what occupies each tile (terrain, roads, building
+// floors/walls/doors, and indoor clutter) plus a parallel walkable grid and
+// the building/road geometry. A single render pass then consumes the buffer
+// to blit sprites, so generation can be inspected, validated, or re-rendered
+// in another format (SVG, ASCII, ...) without decoding a tileset image.
+
+use crate::{Point, Rectangle};
+
+// Tile ids stored in `MapBuffer::tile_types`. Each id names exactly one
+// sprite in gfx/tiles.png; only the render pass knows where.
+pub const GRASS: usize = 0;
+pub const DIRT: usize = 1;
+pub const GRASS_DIRT: usize = 2;
+pub const DIRT_GRASS: usize = 3;
+pub const WATER: usize = 4;
+pub const DIRT_WATER: usize = 5;
+pub const RUBBLE: usize = 6;
+pub const DIRT_RUBBLE: usize = 7;
+pub const ROAD: usize = 8;
+pub const FLOOR: usize = 9;
+pub const WALL_N: usize = 10;
+pub const WALL_S: usize = 11;
+pub const WALL_E: usize = 12;
+pub const WALL_W: usize = 13;
+pub const WALL_NW: usize = 14;
+pub const WALL_NE: usize = 15;
+pub const WALL_SW: usize = 16;
+pub const WALL_SE: usize = 17;
+pub const DOOR_N: usize = 18;
+pub const DOOR_S: usize = 19;
+pub const DOOR_E: usize = 20;
+pub const DOOR_W: usize = 21;
+pub const CRATE: usize = 22;
+pub const BUSH: usize = 23;
+
+// A door is the one tile in a sealed building that's deliberately walkable;
+// anything that claims a tile by type rather than by the obstruction grid
+// (cellular-automata clusters, large-obstacle footprints) needs to steer
+// clear of it or it re-seals the entrance it was carved through.
+pub fn is_door(tile: usize) -> bool {
+    matches!(tile, DOOR_N | DOOR_S | DOOR_E | DOOR_W)
+}
+
+// A building's purpose within the settlement, assigned from a weighted
+// table once it's been sized. Drives which furniture a building gets
+// (modifiers::place_furniture) and is exposed here so any renderer can
+// also read it back (e.g. to label or color-code buildings by role).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BuildingRole {
+    Armory,
+    Tavern,
+    Residence,
+    Warehouse,
+    Abandoned,
+}
+
+#[derive(Copy, Clone)]
+pub struct Building {
+    pub rect: Rectangle,
+    pub role: BuildingRole,
+}
+
+// The footprint of an obstacle that spans more than one tile (a wagon, a
+// boulder pile). `Obstructions` tests and claims the whole footprint, and
+// `MapBuffer::fill_footprint` stamps it by tiling a single sprite across it
+// rather than requiring dedicated multi-tile artwork.
+#[derive(Copy, Clone)]
+pub struct TileSize {
+    pub w: u32,
+    pub h: u32,
+}
+
+pub struct MapBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub tile_types: Vec<usize>,
+    pub walkables: Vec<bool>,
+    pub buildings: Vec<Building>,
+    pub roads: Vec<Vec<Point>>,
+}
+
+impl MapBuffer {
+    pub fn new(width: u32, height: u32) -> MapBuffer {
+        MapBuffer {
+            width,
+            height,
+            tile_types: vec![GRASS; (width * height) as usize],
+            walkables: vec![true; (width * height) as usize],
+            buildings: Vec::new(),
+            roads: Vec::new(),
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    pub fn tile_at(&self, x: u32, y: u32) -> usize {
+        self.tile_types[self.index(x, y)]
+    }
+
+    pub fn set_tile(&mut self, x: u32, y: u32, tile: usize) {
+        let i = self.index(x, y);
+        self.tile_types[i] = tile;
+    }
+
+    // Tiles a single sprite across a multi-tile footprint anchored at
+    // (x, y), so a large obstacle can be stamped without a dedicated
+    // multi-cell sprite.
+    pub fn fill_footprint(&mut self, x: u32, y: u32, size: TileSize, tile: usize) {
+        for fy in y..y + size.h {
+            for fx in x..x + size.w {
+                self.set_tile(fx, fy, tile);
+            }
+        }
+    }
+
+    pub fn is_walkable(&self, x: u32, y: u32) -> bool {
+        self.walkables[self.index(x, y)]
+    }
+
+    pub fn set_walkable(&mut self, x: u32, y: u32, walkable: bool) {
+        let i = self.index(x, y);
+        self.walkables[i] = walkable;
+    }
+
+    // Mirrors the left half of the buffer onto the right half, swapping any
+    // tile id whose sprite is direction-sensitive (walls, doors) so the
+    // model stays logically correct, not just visually flipped.
+    pub fn mirror_vertical(&mut self) {
+        let gen_w = (self.width + 1) / 2;
+        for x in 0..gen_w {
+            let mirror_x = self.width - 1 - x;
+            if mirror_x == x {
+                continue;
+            }
+            for y in 0..self.height {
+                let tile = mirror_tile_horizontal(self.tile_at(x, y));
+                let walkable = self.is_walkable(x, y);
+                self.set_tile(mirror_x, y, tile);
+                self.set_walkable(mirror_x, y, walkable);
+            }
+        }
+    }
+
+    // Mirrors the top half of the buffer onto the bottom half; see
+    // `mirror_vertical` for why tile ids are remapped, not just copied.
+    pub fn mirror_horizontal(&mut self) {
+        let gen_h = (self.height + 1) / 2;
+        for y in 0..gen_h {
+            let mirror_y = self.height - 1 - y;
+            if mirror_y == y {
+                continue;
+            }
+            for x in 0..self.width {
+                let tile = mirror_tile_vertical(self.tile_at(x, y));
+                let walkable = self.is_walkable(x, y);
+                self.set_tile(x, mirror_y, tile);
+                self.set_walkable(x, mirror_y, walkable);
+            }
+        }
+    }
+}
+
+fn mirror_tile_horizontal(tile: usize) -> usize {
+    match tile {
+        WALL_W => WALL_E,
+        WALL_E => WALL_W,
+        WALL_NW => WALL_NE,
+        WALL_NE => WALL_NW,
+        WALL_SW => WALL_SE,
+        WALL_SE => WALL_SW,
+        DOOR_W => DOOR_E,
+        DOOR_E => DOOR_W,
+        other => other,
+    }
+}
+
+fn mirror_tile_vertical(tile: usize) -> usize {
+    match tile {
+        WALL_N => WALL_S,
+        WALL_S => WALL_N,
+        WALL_NW => WALL_SW,
+        WALL_SW => WALL_NW,
+        WALL_NE => WALL_SE,
+        WALL_SE => WALL_NE,
+        DOOR_N => DOOR_S,
+        DOOR_S => DOOR_N,
+        other => other,
+    }
+}
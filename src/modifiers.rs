@@ -0,0 +1,608 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::map::{self, Building, BuildingRole, MapBuffer};
+use crate::{opposite_orientation, Line, Obstructions, Orientation, Point, Rectangle};
+
+// Geometry a modifier contributed, kept alongside the map buffer so
+// non-raster backends (e.g. SVG export) have a source of truth that isn't
+// the pixel buffer. Each modifier reports exactly one variant.
+pub enum Geometry {
+    Terrain(Vec<(u32, u32, usize)>),
+    // Straight road segments, plus any curved connector polylines joining
+    // their dangling ends (both drive the same road rendering, but a curve
+    // isn't expressible as a `Line`).
+    Roads(Vec<Line>, Vec<Vec<Point>>),
+    Buildings(Vec<Building>),
+}
+
+// A single stage of the generation pipeline. Each stage is free to read and
+// write the map buffer and the obstruction grid within `bounds`, and reports
+// the geometry it placed so it can be re-rendered in other formats.
+pub trait MapModifier {
+    fn apply(
+        &self,
+        buffer: &mut MapBuffer,
+        obstructions: &mut Obstructions,
+        bounds: &Rectangle,
+        rng: &mut StdRng,
+    ) -> Geometry;
+}
+
+pub struct TerrainModifier;
+
+impl MapModifier for TerrainModifier {
+    fn apply(
+        &self,
+        buffer: &mut MapBuffer,
+        obstructions: &mut Obstructions,
+        bounds: &Rectangle,
+        rng: &mut StdRng,
+    ) -> Geometry {
+        let terrain_tiles = crate::wfc::default_terrain_tiles();
+        let gen_w = bounds.width();
+        let gen_h = bounds.height();
+        let terrain_grid = crate::wfc::generate_terrain(gen_w, gen_h, &terrain_tiles, rng, 10);
+
+        let mut cells = Vec::new();
+        for x in 0..gen_w {
+            for y in 0..gen_h {
+                let terrain_index = terrain_grid[(y * gen_w + x) as usize];
+                let map_x = bounds.x1 + x;
+                let map_y = bounds.y1 + y;
+                buffer.set_tile(map_x, map_y, terrain_index);
+                // water (4) and rubble (6) are impassable terrain
+                if terrain_index == 4 || terrain_index == 6 {
+                    obstructions.obstruct(map_x, map_y, true);
+                }
+                cells.push((map_x, map_y, terrain_index));
+            }
+        }
+        Geometry::Terrain(cells)
+    }
+}
+
+pub struct RoadModifier {
+    pub count: u32,
+    pub width: u32,
+    pub curved: bool,
+}
+
+impl RoadModifier {
+    fn margin(&self) -> u32 {
+        self.width / 2 + 1
+    }
+}
+
+// The index of the last leading sample of a curved connector that isn't
+// checked for obstruction: those samples start on or right next to the
+// road the connector leaves, which is itself obstructed.
+const SNAP_TOLERANCE: u32 = 2;
+
+// Samples a quadratic Bezier polyline from `a` through control point `c` to
+// `b`, at `steps + 1` evenly spaced values of t (both endpoints included).
+fn quadratic_curve(a: Point, c: Point, b: Point, steps: u32) -> Vec<Point> {
+    let mut points = Vec::new();
+    for i in 0..=steps {
+        let t = i as f64 / steps as f64;
+        let one_minus_t = 1.0 - t;
+        let x = one_minus_t * one_minus_t * a.x as f64
+            + 2.0 * one_minus_t * t * c.x as f64
+            + t * t * b.x as f64;
+        let y = one_minus_t * one_minus_t * a.y as f64
+            + 2.0 * one_minus_t * t * c.y as f64
+            + t * t * b.y as f64;
+        points.push(Point::new(x.round().max(0.0) as u32, y.round().max(0.0) as u32));
+    }
+    points
+}
+
+// The midpoint of `a`-`b`, bulged outward along the perpendicular bisector,
+// used as the quadratic control point for a curved connector.
+fn bulge_control_point(a: Point, b: Point, bounds: &Rectangle, rng: &mut StdRng) -> Point {
+    let mid_x = (a.x + b.x) as f64 / 2.0;
+    let mid_y = (a.y + b.y) as f64 / 2.0;
+    let dx = b.x as f64 - a.x as f64;
+    let dy = b.y as f64 - a.y as f64;
+    let length = (dx * dx + dy * dy).sqrt().max(1.0);
+    let bulge = rng.gen_range(0.15..0.35) * length;
+    let sign = if rng.gen::<bool>() { 1.0 } else { -1.0 };
+    let control_x = mid_x + sign * (-dy / length) * bulge;
+    let control_y = mid_y + sign * (dx / length) * bulge;
+    Point::new(
+        control_x.round().clamp(bounds.x1 as f64, bounds.x2 as f64) as u32,
+        control_y.round().clamp(bounds.y1 as f64, bounds.y2 as f64) as u32,
+    )
+}
+
+fn dangling_endpoints(roads: &[Line], bounds: &Rectangle) -> Vec<Point> {
+    let mut endpoints = Vec::new();
+    for road in roads {
+        let (start, end) = match road.orientation {
+            Orientation::Horiz => (Point::new(road.x, road.y), Point::new(road.x + road.length - 1, road.y)),
+            Orientation::Vert => (Point::new(road.x, road.y), Point::new(road.x, road.y + road.length - 1)),
+        };
+        for point in [start, end] {
+            if point.x > bounds.x1 && point.x < bounds.x2 && point.y > bounds.y1 && point.y < bounds.y2 {
+                endpoints.push(point);
+            }
+        }
+    }
+    endpoints
+}
+
+// Draws a curved connector from one dangling road endpoint toward another,
+// stopping short of the first obstructed tile (a road or impassable
+// terrain) it reaches past the first `SNAP_TOLERANCE` samples. Endpoints
+// are not moved onto a nearby road; a connector ends where it stops.
+fn draw_curved_connector(buffer: &mut MapBuffer, obstructions: &mut Obstructions, width: u32, a: Point, b: Point, bounds: &Rectangle, rng: &mut StdRng, path: &mut Vec<Point>) {
+    let control = bulge_control_point(a, b, bounds, rng);
+    let steps = 12;
+    let curve = quadratic_curve(a, control, b, steps);
+    for (i, point) in curve.iter().enumerate() {
+        if point.x < bounds.x1 || point.x > bounds.x2 || point.y < bounds.y1 || point.y > bounds.y2 {
+            break;
+        }
+        if i > 0 && *point == curve[i - 1] {
+            // rounding repeated the previous sample, which is already drawn
+            continue;
+        }
+        if i as u32 > SNAP_TOLERANCE && obstructions.is_obstructed(point.x, point.y) {
+            // reached an existing road or impassable terrain; stop short of it
+            break;
+        }
+        obstructions.obstruct(point.x, point.y, true);
+        path.push(*point);
+        let half = width / 2;
+        for wx in 0..width {
+            for wy in 0..width {
+                let tx = point.x + wx;
+                let ty = point.y + wy;
+                if tx >= half && ty >= half && tx - half <= bounds.x2 && ty - half <= bounds.y2 {
+                    buffer.set_tile(tx - half, ty - half, map::ROAD);
+                }
+            }
+        }
+    }
+}
+
+impl MapModifier for RoadModifier {
+    fn apply(
+        &self,
+        buffer: &mut MapBuffer,
+        obstructions: &mut Obstructions,
+        bounds: &Rectangle,
+        rng: &mut StdRng,
+    ) -> Geometry {
+        let roads = bounds.divide_with_lines(self.count, self.margin(), rng);
+
+        for road in &roads {
+            let mut x = road.x;
+            let mut y = road.y;
+            let mut path = Vec::new();
+            for _t in 0..road.length {
+                obstructions.obstruct(x, y, true);
+                path.push(Point::new(x, y));
+                match road.orientation {
+                    Orientation::Horiz => {
+                        for w in 0..self.width {
+                            buffer.set_tile(x, y - (self.width / 2) + w, map::ROAD);
+                        }
+                        for w in 0..self.margin() {
+                            obstructions.obstruct(x, y - w, true);
+                            obstructions.obstruct(x, y + w, true);
+                        }
+                        x += 1;
+                    }
+                    Orientation::Vert => {
+                        for w in 0..self.width {
+                            buffer.set_tile(x - (self.width / 2) + w, y, map::ROAD);
+                        }
+                        for w in 0..self.margin() {
+                            obstructions.obstruct(x - w, y, true);
+                            obstructions.obstruct(x + w, y, true);
+                        }
+                        y += 1;
+                    }
+                }
+            }
+            buffer.roads.push(path);
+        }
+
+        let mut curves: Vec<Vec<Point>> = Vec::new();
+        if self.curved {
+            let endpoints = dangling_endpoints(&roads, bounds);
+            let mut connected = vec![false; endpoints.len()];
+            for i in 0..endpoints.len() {
+                if connected[i] {
+                    continue;
+                }
+                let mut nearest: Option<(usize, u32)> = None;
+                for j in 0..endpoints.len() {
+                    if i == j || connected[j] {
+                        continue;
+                    }
+                    let dx = endpoints[i].x as i64 - endpoints[j].x as i64;
+                    let dy = endpoints[i].y as i64 - endpoints[j].y as i64;
+                    let dist = (dx * dx + dy * dy) as u32;
+                    if nearest.map_or(true, |(_, best)| dist < best) {
+                        nearest = Some((j, dist));
+                    }
+                }
+                if let Some((j, _)) = nearest {
+                    let mut path = Vec::new();
+                    draw_curved_connector(buffer, obstructions, self.width, endpoints[i], endpoints[j], bounds, rng, &mut path);
+                    buffer.roads.push(path.clone());
+                    curves.push(path);
+                    connected[i] = true;
+                    connected[j] = true;
+                }
+            }
+        }
+
+        Geometry::Roads(roads, curves)
+    }
+}
+
+pub struct BuildingModifier {
+    pub count: u32,
+    pub max_size: u32,
+    pub style: crate::BuildingStyle,
+}
+
+impl MapModifier for BuildingModifier {
+    fn apply(
+        &self,
+        buffer: &mut MapBuffer,
+        obstructions: &mut Obstructions,
+        bounds: &Rectangle,
+        rng: &mut StdRng,
+    ) -> Geometry {
+        let mut buildings = Vec::new();
+        for _b in 0..self.count {
+            let mut building = obstructions.find_clear_rectangle_within(bounds, 3, self.max_size, rng);
+            building.shrink(1);
+            let door_count = building.perimeter() / 20 + 1;
+            let mut doors = Vec::new();
+            for _d in 0..door_count {
+                doors.push(building.find_exterior_point(rng));
+            }
+            for x in building.x1..building.x2 + 1 {
+                for y in building.y1..building.y2 + 1 {
+                    buffer.set_tile(x, y, map::FLOOR);
+                    let point = Point::new(x, y);
+                    if doors.contains(&point) {
+                        if x == building.x1 {
+                            buffer.set_tile(x, y, map::DOOR_W);
+                        } else if x == building.x2 {
+                            buffer.set_tile(x, y, map::DOOR_E);
+                        } else if y == building.y1 {
+                            buffer.set_tile(x, y, map::DOOR_N);
+                        } else {
+                            buffer.set_tile(x, y, map::DOOR_S);
+                        }
+                    } else if x == building.x1 {
+                        if y == building.y1 {
+                            buffer.set_tile(x, y, map::WALL_NW);
+                        } else if y == building.y2 {
+                            buffer.set_tile(x, y, map::WALL_SW);
+                        } else {
+                            buffer.set_tile(x, y, map::WALL_W);
+                        }
+                    } else if x == building.x2 {
+                        if y == building.y1 {
+                            buffer.set_tile(x, y, map::WALL_NE);
+                        } else if y == building.y2 {
+                            buffer.set_tile(x, y, map::WALL_SE);
+                        } else {
+                            buffer.set_tile(x, y, map::WALL_E);
+                        }
+                    } else if y == building.y1 {
+                        buffer.set_tile(x, y, map::WALL_N);
+                    } else if y == building.y2 {
+                        buffer.set_tile(x, y, map::WALL_S);
+                    }
+                    obstructions.obstruct(x, y, true);
+                }
+            }
+
+            match self.style {
+                crate::BuildingStyle::Rooms => {
+                    let wall_count = building.area() / 30;
+                    let walls = building.divide_with_lines(wall_count, 3, rng);
+                    for wall in &walls {
+                        let mut draw_length = wall.length;
+                        match wall.orientation {
+                            Orientation::Horiz => {
+                                if wall.x > building.x1 && wall.x + wall.length <= building.x2 {
+                                    draw_length -= 1;
+                                }
+                            }
+                            Orientation::Vert => {
+                                if wall.y > building.y1 && wall.y + wall.length <= building.y2 {
+                                    draw_length -= 1;
+                                }
+                            }
+                        }
+                        let mut door = 1;
+                        if draw_length > 3 {
+                            door = rng.gen_range(1..draw_length - 2);
+                        }
+                        for l in 0..draw_length {
+                            if l == door {
+                                match wall.orientation {
+                                    Orientation::Horiz => {
+                                        buffer.set_tile(wall.x + l, wall.y, map::DOOR_N);
+                                    }
+                                    Orientation::Vert => {
+                                        buffer.set_tile(wall.x, wall.y + l, map::DOOR_W);
+                                    }
+                                }
+                            } else {
+                                match wall.orientation {
+                                    Orientation::Horiz => {
+                                        buffer.set_tile(wall.x + l, wall.y, map::WALL_N);
+                                    }
+                                    Orientation::Vert => {
+                                        buffer.set_tile(wall.x, wall.y + l, map::WALL_W);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                crate::BuildingStyle::Maze => {
+                    crate::maze::carve(buffer, &building, rng);
+                }
+            }
+
+            let role = choose_role(rng);
+            place_furniture(buffer, &building, role, rng);
+
+            buffer.buildings.push(Building { rect: building, role });
+            buildings.push(Building { rect: building, role });
+        }
+
+        Geometry::Buildings(buildings)
+    }
+}
+
+// How often each role comes up, out of the total weight. Residences and
+// warehouses are the bulk of any settlement; armories are rare, and a
+// handful of buildings are abandoned outright.
+const BUILDING_ROLES: [(BuildingRole, u32); 5] = [
+    (BuildingRole::Residence, 40),
+    (BuildingRole::Warehouse, 20),
+    (BuildingRole::Tavern, 15),
+    (BuildingRole::Abandoned, 15),
+    (BuildingRole::Armory, 10),
+];
+
+fn choose_role(rng: &mut StdRng) -> BuildingRole {
+    let total: u32 = BUILDING_ROLES.iter().map(|(_, weight)| *weight).sum();
+    let mut roll = rng.gen_range(0..total);
+    for (role, weight) in BUILDING_ROLES {
+        if roll < weight {
+            return role;
+        }
+        roll -= weight;
+    }
+    BuildingRole::Residence
+}
+
+// Dresses a building's interior to match its role. Every placement skips
+// tiles that aren't bare floor, so furniture never overwrites a wall or
+// door regardless of whether the interior is room-divided or a maze.
+fn place_furniture(buffer: &mut MapBuffer, building: &Rectangle, role: BuildingRole, rng: &mut StdRng) {
+    match role {
+        BuildingRole::Tavern => place_tavern_tables(buffer, building),
+        BuildingRole::Warehouse => place_warehouse_crates(buffer, building),
+        BuildingRole::Armory => place_armory_rack(buffer, building),
+        BuildingRole::Abandoned => place_scattered_clutter(buffer, building, rng, building.area() / 15, map::RUBBLE),
+        BuildingRole::Residence => place_scattered_clutter(buffer, building, rng, building.area() / 50, map::CRATE),
+    }
+}
+
+// Tavern: tables in a regular grid, spaced so patrons can walk between rows.
+fn place_tavern_tables(buffer: &mut MapBuffer, building: &Rectangle) {
+    let mut y = building.y1 + 2;
+    while y < building.y2 {
+        let mut x = building.x1 + 2;
+        while x < building.x2 {
+            if buffer.tile_at(x, y) == map::FLOOR {
+                buffer.set_tile(x, y, map::CRATE);
+            }
+            x += 3;
+        }
+        y += 2;
+    }
+}
+
+// Warehouse: crates stacked along every interior wall, stock piled floor to
+// ceiling rather than left in the open.
+fn place_warehouse_crates(buffer: &mut MapBuffer, building: &Rectangle) {
+    for x in building.x1 + 1..building.x2 {
+        for y in [building.y1 + 1, building.y2 - 1] {
+            if buffer.tile_at(x, y) == map::FLOOR {
+                buffer.set_tile(x, y, map::CRATE);
+            }
+        }
+    }
+    for y in building.y1 + 1..building.y2 {
+        for x in [building.x1 + 1, building.x2 - 1] {
+            if buffer.tile_at(x, y) == map::FLOOR {
+                buffer.set_tile(x, y, map::CRATE);
+            }
+        }
+    }
+}
+
+// Armory: a single rack down the middle of the longer axis.
+fn place_armory_rack(buffer: &mut MapBuffer, building: &Rectangle) {
+    if building.width() >= building.height() {
+        let y = (building.y1 + building.y2) / 2;
+        for x in building.x1 + 1..building.x2 {
+            if buffer.tile_at(x, y) == map::FLOOR {
+                buffer.set_tile(x, y, map::CRATE);
+            }
+        }
+    } else {
+        let x = (building.x1 + building.x2) / 2;
+        for y in building.y1 + 1..building.y2 {
+            if buffer.tile_at(x, y) == map::FLOOR {
+                buffer.set_tile(x, y, map::CRATE);
+            }
+        }
+    }
+}
+
+// Residence/abandoned: `count` props dropped at random clear floor tiles.
+fn place_scattered_clutter(buffer: &mut MapBuffer, building: &Rectangle, rng: &mut StdRng, count: u32, tile: usize) {
+    for _c in 0..count {
+        let mut thing = Point::new(0, 0);
+        let mut finding = true;
+        while finding {
+            finding = false;
+            thing = building.find_point_within(1, rng);
+            if buffer.tile_at(thing.x, thing.y) != map::FLOOR {
+                finding = true;
+            }
+        }
+        buffer.set_tile(thing.x, thing.y, tile);
+    }
+}
+
+// Mirrors the generated half/quadrant across the map center: copies the
+// obstruction grid across, and mirrors the map buffer, which remaps
+// direction-sensitive tile ids (walls, doors) so they face the right way.
+// Cars are overlaid after rendering and are not mirrored here.
+pub fn mirror_map(buffer: &mut MapBuffer, obstructions: &mut Obstructions, w: u32, h: u32, symmetry: crate::Symmetry) {
+    if symmetry == crate::Symmetry::Vertical || symmetry == crate::Symmetry::Both {
+        mirror_vertical(buffer, obstructions, w, h);
+    }
+    if symmetry == crate::Symmetry::Horizontal || symmetry == crate::Symmetry::Both {
+        mirror_horizontal(buffer, obstructions, w, h);
+    }
+}
+
+fn mirror_vertical(buffer: &mut MapBuffer, obstructions: &mut Obstructions, w: u32, h: u32) {
+    let gen_w = (w + 1) / 2;
+    for x in 0..gen_w {
+        let mirror_x = w - 1 - x;
+        if mirror_x == x {
+            continue;
+        }
+        for y in 0..h {
+            obstructions.obstruct(mirror_x, y, obstructions.is_obstructed(x, y));
+        }
+    }
+    buffer.mirror_vertical();
+}
+
+fn mirror_horizontal(buffer: &mut MapBuffer, obstructions: &mut Obstructions, w: u32, h: u32) {
+    let gen_h = (h + 1) / 2;
+    for y in 0..gen_h {
+        let mirror_y = h - 1 - y;
+        if mirror_y == y {
+            continue;
+        }
+        for x in 0..w {
+            obstructions.obstruct(x, mirror_y, obstructions.is_obstructed(x, y));
+        }
+    }
+    buffer.mirror_horizontal();
+}
+
+// Extends a building list the same way `mirror_map` extends the tile grid:
+// sequential vertical-then-horizontal reflection, so a building placed in
+// the generated half/quadrant gets a matching entry for every copy
+// `mirror_map` stamps elsewhere on the map. Connectivity must see all of
+// them, not just the originals, or buildings in a mirrored quadrant never
+// get checked for a door.
+pub fn mirror_buildings(buildings: &[Building], w: u32, h: u32, symmetry: crate::Symmetry) -> Vec<Building> {
+    let mut all = buildings.to_vec();
+    if symmetry == crate::Symmetry::Vertical || symmetry == crate::Symmetry::Both {
+        let mirrored: Vec<Building> = all.iter().map(|b| Building {
+            rect: Rectangle { x1: w - 1 - b.rect.x2, y1: b.rect.y1, x2: w - 1 - b.rect.x1, y2: b.rect.y2 },
+            role: b.role,
+        }).collect();
+        all.extend(mirrored);
+    }
+    if symmetry == crate::Symmetry::Horizontal || symmetry == crate::Symmetry::Both {
+        let mirrored: Vec<Building> = all.iter().map(|b| Building {
+            rect: Rectangle { x1: b.rect.x1, y1: h - 1 - b.rect.y2, x2: b.rect.x2, y2: h - 1 - b.rect.y1 },
+            role: b.role,
+        }).collect();
+        all.extend(mirrored);
+    }
+    all
+}
+
+// Same sequential vertical-then-horizontal doubling as `mirror_buildings`,
+// applied to the terrain cell list a `TerrainModifier` reports, so a
+// geometry-only consumer (SVG export) sees the same full map area the
+// mirrored `MapBuffer`/`Obstructions` do instead of just the generated
+// half/quadrant.
+pub fn mirror_terrain(cells: &[(u32, u32, usize)], w: u32, h: u32, symmetry: crate::Symmetry) -> Vec<(u32, u32, usize)> {
+    let mut all = cells.to_vec();
+    if symmetry == crate::Symmetry::Vertical || symmetry == crate::Symmetry::Both {
+        let mirrored: Vec<(u32, u32, usize)> = all.iter().map(|&(x, y, t)| (w - 1 - x, y, t)).collect();
+        all.extend(mirrored);
+    }
+    if symmetry == crate::Symmetry::Horizontal || symmetry == crate::Symmetry::Both {
+        let mirrored: Vec<(u32, u32, usize)> = all.iter().map(|&(x, y, t)| (x, h - 1 - y, t)).collect();
+        all.extend(mirrored);
+    }
+    all
+}
+
+fn mirror_line_vertical(line: Line, w: u32) -> Line {
+    match line.orientation {
+        // fixed y, spans x: reflecting every point on the span moves its
+        // origin to the mirror of its far end, not the mirror of `x` itself.
+        Orientation::Horiz => Line { x: w - line.x - line.length, y: line.y, orientation: line.orientation, length: line.length },
+        Orientation::Vert => Line { x: w - 1 - line.x, y: line.y, orientation: line.orientation, length: line.length },
+    }
+}
+
+fn mirror_line_horizontal(line: Line, h: u32) -> Line {
+    match line.orientation {
+        Orientation::Horiz => Line { x: line.x, y: h - 1 - line.y, orientation: line.orientation, length: line.length },
+        Orientation::Vert => Line { x: line.x, y: h - line.y - line.length, orientation: line.orientation, length: line.length },
+    }
+}
+
+// Mirrors the straight road segments a `RoadModifier` reports, the same way
+// `mirror_buildings` mirrors buildings.
+pub fn mirror_lines(lines: &[Line], w: u32, h: u32, symmetry: crate::Symmetry) -> Vec<Line> {
+    let mut all = lines.to_vec();
+    if symmetry == crate::Symmetry::Vertical || symmetry == crate::Symmetry::Both {
+        let mirrored: Vec<Line> = all.iter().map(|&l| mirror_line_vertical(l, w)).collect();
+        all.extend(mirrored);
+    }
+    if symmetry == crate::Symmetry::Horizontal || symmetry == crate::Symmetry::Both {
+        let mirrored: Vec<Line> = all.iter().map(|&l| mirror_line_horizontal(l, h)).collect();
+        all.extend(mirrored);
+    }
+    all
+}
+
+// Mirrors point-by-point paths (curved road connectors, and
+// `MapBuffer::roads`' point trace of every road) the same way: each path is
+// reflected as a whole, not its individual points independently re-ordered.
+pub fn mirror_point_paths(paths: &[Vec<Point>], w: u32, h: u32, symmetry: crate::Symmetry) -> Vec<Vec<Point>> {
+    let mut all = paths.to_vec();
+    if symmetry == crate::Symmetry::Vertical || symmetry == crate::Symmetry::Both {
+        let mirrored: Vec<Vec<Point>> = all.iter()
+            .map(|path| path.iter().map(|p| Point::new(w - 1 - p.x, p.y)).collect())
+            .collect();
+        all.extend(mirrored);
+    }
+    if symmetry == crate::Symmetry::Horizontal || symmetry == crate::Symmetry::Both {
+        let mirrored: Vec<Vec<Point>> = all.iter()
+            .map(|path| path.iter().map(|p| Point::new(p.x, h - 1 - p.y)).collect())
+            .collect();
+        all.extend(mirrored);
+    }
+    all
+}
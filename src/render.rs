@@ -0,0 +1,55 @@
+// Rendering pass: the only module that knows where each `MapBuffer` tile id
+// lives in gfx/tiles.png. Kept separate from generation so the same buffer
+// can be blitted to raster, or (in text.rs) rendered as ASCII without ever
+// touching a tileset image. SVG export works from the modifiers' geometry,
+// not from the buffer.
+
+use image::{DynamicImage, RgbaImage};
+
+use crate::map::{self, MapBuffer};
+use crate::TILE_SIZE;
+
+fn crop(tiles: &DynamicImage, x: u32, y: u32) -> DynamicImage {
+    tiles.crop_imm(x, y, TILE_SIZE, TILE_SIZE)
+}
+
+fn sprite_for(tile: usize, tiles: &DynamicImage) -> DynamicImage {
+    match tile {
+        map::GRASS => crop(tiles, 0, 128),
+        map::DIRT => crop(tiles, 32, 128),
+        map::GRASS_DIRT => crop(tiles, 64, 128),
+        map::DIRT_GRASS => crop(tiles, 96, 128),
+        map::WATER => crop(tiles, 128, 128),
+        map::DIRT_WATER => crop(tiles, 160, 128),
+        map::RUBBLE => crop(tiles, 192, 128),
+        map::DIRT_RUBBLE => crop(tiles, 224, 128),
+        map::ROAD => crop(tiles, 32, 0),
+        map::FLOOR => crop(tiles, 96, 0),
+        map::WALL_NW => crop(tiles, 0, 96),
+        map::WALL_NE => crop(tiles, 32, 96),
+        map::WALL_SW => crop(tiles, 64, 96),
+        map::WALL_SE => crop(tiles, 96, 96),
+        map::WALL_N => crop(tiles, 128, 96),
+        map::WALL_S => crop(tiles, 160, 96),
+        map::WALL_W => crop(tiles, 192, 96),
+        map::WALL_E => crop(tiles, 224, 96),
+        map::DOOR_W => crop(tiles, 0, 64),
+        map::DOOR_N => crop(tiles, 32, 64),
+        map::DOOR_E => crop(tiles, 64, 64),
+        map::DOOR_S => crop(tiles, 96, 64),
+        map::CRATE => crop(tiles, 0, 32),
+        map::BUSH => crop(tiles, 32, 32),
+        _ => crop(tiles, 0, 128),
+    }
+}
+
+pub fn render(buffer: &MapBuffer, tiles: &DynamicImage) -> RgbaImage {
+    let mut img = RgbaImage::new(buffer.width * TILE_SIZE, buffer.height * TILE_SIZE);
+    for y in 0..buffer.height {
+        for x in 0..buffer.width {
+            let sprite = sprite_for(buffer.tile_at(x, y), tiles);
+            image::imageops::overlay(&mut img, &sprite, x * TILE_SIZE, y * TILE_SIZE);
+        }
+    }
+    img
+}
@@ -0,0 +1,94 @@
+// Vector export: translates the generator's geometry directly into layered
+// SVG instead of blitting 32px tile bitmaps, so the result stays tiny and
+// scalable and can be edited in Inkscape/Foundry.
+
+use crate::map::BuildingRole;
+use crate::modifiers::Geometry;
+use crate::{Orientation, TILE_SIZE};
+
+fn terrain_color(tile_index: usize) -> &'static str {
+    match tile_index {
+        0 => "#6b9e45", // grass
+        1 => "#8a6b4a", // dirt
+        2 | 3 => "#7a8a42", // grass/dirt transition
+        4 => "#3d6e9e", // water
+        5 => "#5a7a6e", // dirt/water transition
+        6 => "#555555", // rubble
+        7 => "#6e6452", // dirt/rubble transition
+        _ => "#8a6b4a"
+    }
+}
+
+fn building_color(role: BuildingRole) -> &'static str {
+    match role {
+        BuildingRole::Armory => "#8a5a5a",
+        BuildingRole::Tavern => "#b58a4a",
+        BuildingRole::Residence => "#c9bfa5",
+        BuildingRole::Warehouse => "#a5976e",
+        BuildingRole::Abandoned => "#8a8a84",
+    }
+}
+
+pub fn render(w: u32, h: u32, road_width: u32, geometry: &[Geometry]) -> String {
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        w * TILE_SIZE, h * TILE_SIZE, w * TILE_SIZE, h * TILE_SIZE
+    ));
+
+    for layer in geometry {
+        match layer {
+            Geometry::Terrain(cells) => {
+                svg.push_str("  <g id=\"terrain\">\n");
+                for (x, y, tile_index) in cells {
+                    svg.push_str(&format!(
+                        "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" />\n",
+                        x * TILE_SIZE, y * TILE_SIZE, TILE_SIZE, TILE_SIZE, terrain_color(*tile_index)
+                    ));
+                }
+                svg.push_str("  </g>\n");
+            },
+            Geometry::Roads(roads, curves) => {
+                svg.push_str("  <g id=\"roads\">\n");
+                for road in roads {
+                    let (x1, y1, x2, y2) = match road.orientation {
+                        Orientation::Horiz => (road.x, road.y, road.x + road.length, road.y),
+                        Orientation::Vert => (road.x, road.y, road.x, road.y + road.length)
+                    };
+                    svg.push_str(&format!(
+                        "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#9a8f7a\" stroke-width=\"{}\" stroke-linecap=\"square\" />\n",
+                        x1 * TILE_SIZE + TILE_SIZE / 2, y1 * TILE_SIZE + TILE_SIZE / 2,
+                        x2 * TILE_SIZE + TILE_SIZE / 2, y2 * TILE_SIZE + TILE_SIZE / 2,
+                        road_width * TILE_SIZE
+                    ));
+                }
+                for curve in curves {
+                    let points: Vec<String> = curve.iter()
+                        .map(|p| format!("{},{}", p.x * TILE_SIZE + TILE_SIZE / 2, p.y * TILE_SIZE + TILE_SIZE / 2))
+                        .collect();
+                    svg.push_str(&format!(
+                        "    <polyline points=\"{}\" fill=\"none\" stroke=\"#9a8f7a\" stroke-width=\"{}\" stroke-linecap=\"round\" stroke-linejoin=\"round\" />\n",
+                        points.join(" "), road_width * TILE_SIZE
+                    ));
+                }
+                svg.push_str("  </g>\n");
+            },
+            Geometry::Buildings(buildings) => {
+                svg.push_str("  <g id=\"buildings\">\n");
+                for building in buildings {
+                    let rect = building.rect;
+                    svg.push_str(&format!(
+                        "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"#3a332a\" stroke-width=\"3\" />\n",
+                        rect.x1 * TILE_SIZE, rect.y1 * TILE_SIZE,
+                        rect.width() * TILE_SIZE, rect.height() * TILE_SIZE,
+                        building_color(building.role)
+                    ));
+                }
+                svg.push_str("  </g>\n");
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,60 @@
+// Plain-text export: one glyph per tile, driven entirely off the abstract
+// `MapBuffer` tile grid. Dependency-light and line-diffable, so it's a fast
+// way to sanity-check generation or write a regression test without
+// decoding a tileset image or an SVG document.
+
+use crate::map::{self, MapBuffer};
+
+fn glyph_for(tile: usize) -> char {
+    match tile {
+        map::GRASS | map::DIRT | map::GRASS_DIRT | map::DIRT_GRASS | map::FLOOR => '.',
+        map::WATER | map::DIRT_WATER => '~',
+        map::RUBBLE | map::DIRT_RUBBLE => ',',
+        map::ROAD => ';',
+        map::WALL_N | map::WALL_S | map::WALL_E | map::WALL_W
+            | map::WALL_NW | map::WALL_NE | map::WALL_SW | map::WALL_SE => '#',
+        map::DOOR_N | map::DOOR_S | map::DOOR_E | map::DOOR_W => '+',
+        map::CRATE => '*',
+        map::BUSH => '"',
+        _ => '?',
+    }
+}
+
+pub fn render(buffer: &MapBuffer) -> String {
+    let mut text = String::new();
+    for y in 0..buffer.height {
+        for x in 0..buffer.width {
+            text.push(glyph_for(buffer.tile_at(x, y)));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyph_for_maps_every_family_of_tile() {
+        assert_eq!(glyph_for(map::GRASS), '.');
+        assert_eq!(glyph_for(map::FLOOR), '.');
+        assert_eq!(glyph_for(map::WATER), '~');
+        assert_eq!(glyph_for(map::RUBBLE), ',');
+        assert_eq!(glyph_for(map::ROAD), ';');
+        assert_eq!(glyph_for(map::WALL_NW), '#');
+        assert_eq!(glyph_for(map::DOOR_N), '+');
+        assert_eq!(glyph_for(map::CRATE), '*');
+        assert_eq!(glyph_for(map::BUSH), '"');
+    }
+
+    #[test]
+    fn render_emits_one_glyph_row_per_buffer_row() {
+        let mut buffer = MapBuffer::new(3, 2);
+        buffer.set_tile(0, 0, map::WATER);
+        buffer.set_tile(1, 1, map::DOOR_N);
+        let rendered = render(&buffer);
+        let rows: Vec<&str> = rendered.lines().collect();
+        assert_eq!(rows, vec!["~..", ".+."]);
+    }
+}
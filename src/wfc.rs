@@ -0,0 +1,279 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// Edge sockets, in N/E/S/W order. Two tiles may sit next to each other only
+// if the touching sockets match.
+const NORTH: usize = 0;
+const EAST: usize = 1;
+const SOUTH: usize = 2;
+const WEST: usize = 3;
+
+pub struct TerrainTile {
+    pub tile_index: usize,
+    pub edges: [u8; 4],
+    pub weight: u32,
+}
+
+// A tileset descriptor entry: one sprite's edge labels plus which
+// symmetries it's legal to place it under. `expand_tile_set` turns a list
+// of these into the `TerrainTile` variants the WFC grid actually chooses
+// between, so a caller only has to describe a tile once instead of writing
+// out every rotation/mirror of it by hand.
+pub struct TileDefinition {
+    pub tile_index: usize,
+    pub edges: [u8; 4],
+    pub weight: u32,
+    pub can_flip: bool,
+    pub can_mirror: bool,
+    pub can_rotate90: bool,
+    pub can_rotate180: bool,
+    pub can_rotate270: bool,
+}
+
+fn rotate90(edges: [u8; 4]) -> [u8; 4] {
+    [edges[WEST], edges[NORTH], edges[EAST], edges[SOUTH]]
+}
+
+fn flip_horizontal(edges: [u8; 4]) -> [u8; 4] {
+    [edges[NORTH], edges[WEST], edges[SOUTH], edges[EAST]]
+}
+
+fn flip_vertical(edges: [u8; 4]) -> [u8; 4] {
+    [edges[SOUTH], edges[EAST], edges[NORTH], edges[WEST]]
+}
+
+// Expands each definition into the allowed transformed variants: rotations
+// stack (rotate180 is two 90s, rotate270 is three), flips apply to the
+// untouched original. Variants whose edges turn out identical to one
+// already kept (always true for a symmetric tile, like a uniform grass
+// tile under any transform) are skipped.
+pub fn expand_tile_set(defs: &[TileDefinition]) -> Vec<TerrainTile> {
+    let mut expanded = Vec::new();
+    for def in defs {
+        let mut seen: Vec<[u8; 4]> = Vec::new();
+        let mut variants = vec![def.edges];
+        if def.can_rotate90 {
+            variants.push(rotate90(def.edges));
+        }
+        if def.can_rotate180 {
+            variants.push(rotate90(rotate90(def.edges)));
+        }
+        if def.can_rotate270 {
+            variants.push(rotate90(rotate90(rotate90(def.edges))));
+        }
+        if def.can_flip {
+            variants.push(flip_horizontal(def.edges));
+        }
+        if def.can_mirror {
+            variants.push(flip_vertical(def.edges));
+        }
+        for edges in variants {
+            if !seen.contains(&edges) {
+                seen.push(edges);
+                expanded.push(TerrainTile { tile_index: def.tile_index, edges, weight: def.weight });
+            }
+        }
+    }
+    expanded
+}
+
+pub fn default_terrain_tiles() -> Vec<TerrainTile> {
+    // Socket ids: 0 = grass, 1 = dirt, 2 = water, 3 = rubble. The four
+    // uniform tiles look the same under every symmetry, so enabling all
+    // the flags just confirms there's nothing to expand; the diagonal
+    // transition tiles have their own distinct sprite per orientation
+    // (e.g. grass/dirt vs. dirt/grass) so they're left unflagged and
+    // listed individually instead.
+    expand_tile_set(&[
+        TileDefinition { tile_index: 0, edges: [0, 0, 0, 0], weight: 30, can_flip: true, can_mirror: true, can_rotate90: true, can_rotate180: true, can_rotate270: true }, // grass
+        TileDefinition { tile_index: 1, edges: [1, 1, 1, 1], weight: 20, can_flip: true, can_mirror: true, can_rotate90: true, can_rotate180: true, can_rotate270: true }, // dirt
+        TileDefinition { tile_index: 2, edges: [0, 1, 1, 0], weight: 6, can_flip: false, can_mirror: false, can_rotate90: false, can_rotate180: false, can_rotate270: false },  // grass/dirt transition
+        TileDefinition { tile_index: 3, edges: [1, 0, 0, 1], weight: 6, can_flip: false, can_mirror: false, can_rotate90: false, can_rotate180: false, can_rotate270: false },  // dirt/grass transition
+        TileDefinition { tile_index: 4, edges: [2, 2, 2, 2], weight: 4, can_flip: true, can_mirror: true, can_rotate90: true, can_rotate180: true, can_rotate270: true }, // water
+        TileDefinition { tile_index: 5, edges: [1, 2, 2, 1], weight: 3, can_flip: false, can_mirror: false, can_rotate90: false, can_rotate180: false, can_rotate270: false },  // dirt/water transition
+        TileDefinition { tile_index: 6, edges: [3, 3, 3, 3], weight: 3, can_flip: true, can_mirror: true, can_rotate90: true, can_rotate180: true, can_rotate270: true }, // rubble
+        TileDefinition { tile_index: 7, edges: [1, 3, 3, 1], weight: 3, can_flip: false, can_mirror: false, can_rotate90: false, can_rotate180: false, can_rotate270: false },  // dirt/rubble transition
+    ])
+}
+
+fn opposite_socket(direction: usize) -> usize {
+    match direction {
+        NORTH => SOUTH,
+        SOUTH => NORTH,
+        EAST => WEST,
+        _ => EAST,
+    }
+}
+
+fn neighbor_offset(direction: usize) -> (i32, i32) {
+    match direction {
+        NORTH => (0, -1),
+        SOUTH => (0, 1),
+        EAST => (1, 0),
+        _ => (-1, 0),
+    }
+}
+
+pub struct WfcGrid {
+    w: u32,
+    h: u32,
+    possibilities: Vec<Vec<usize>>,
+}
+
+impl WfcGrid {
+    fn new(w: u32, h: u32, tile_count: usize) -> WfcGrid {
+        let all: Vec<usize> = (0..tile_count).collect();
+        WfcGrid { w, h, possibilities: vec![all; (w * h) as usize] }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.w + x) as usize
+    }
+
+    fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as u32) < self.w && (y as u32) < self.h
+    }
+
+    fn lowest_entropy_cell(&self, rng: &mut StdRng) -> Option<(u32, u32)> {
+        let mut best: Option<(u32, u32)> = None;
+        let mut best_count = usize::MAX;
+        let mut ties: Vec<(u32, u32)> = Vec::new();
+        for y in 0..self.h {
+            for x in 0..self.w {
+                let count = self.possibilities[self.index(x, y)].len();
+                if count > 1 {
+                    if count < best_count {
+                        best_count = count;
+                        ties.clear();
+                        ties.push((x, y));
+                    } else if count == best_count {
+                        ties.push((x, y));
+                    }
+                }
+            }
+        }
+        if !ties.is_empty() {
+            best = Some(ties[rng.gen_range(0..ties.len())]);
+        }
+        best
+    }
+
+    fn collapse(&mut self, x: u32, y: u32, tiles: &[TerrainTile], rng: &mut StdRng) -> usize {
+        let options = &self.possibilities[self.index(x, y)];
+        let total_weight: u32 = options.iter().map(|&i| tiles[i].weight).sum();
+        let mut roll = rng.gen_range(0..total_weight.max(1));
+        let mut chosen = options[0];
+        for &option in options {
+            if roll < tiles[option].weight {
+                chosen = option;
+                break;
+            }
+            roll -= tiles[option].weight;
+        }
+        self.possibilities[self.index(x, y)] = vec![chosen];
+        chosen
+    }
+
+    fn propagate(&mut self, start: (u32, u32), tiles: &[TerrainTile]) -> bool {
+        let mut stack = vec![start];
+        while let Some((x, y)) = stack.pop() {
+            let current_options = self.possibilities[self.index(x, y)].clone();
+            for direction in 0..4 {
+                let (dx, dy) = neighbor_offset(direction);
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if !self.in_bounds(nx, ny) {
+                    continue;
+                }
+                let (nx, ny) = (nx as u32, ny as u32);
+                let opposite = opposite_socket(direction);
+                let allowed_sockets: Vec<u8> = current_options
+                    .iter()
+                    .map(|&i| tiles[i].edges[direction])
+                    .collect();
+                let idx = self.index(nx, ny);
+                let before_len = self.possibilities[idx].len();
+                self.possibilities[idx].retain(|&option| {
+                    allowed_sockets.contains(&tiles[option].edges[opposite])
+                });
+                if self.possibilities[idx].is_empty() {
+                    return false;
+                }
+                if self.possibilities[idx].len() < before_len {
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        true
+    }
+}
+
+// Generates a w*h grid of tile indices (into `tiles`) using Wave Function
+// Collapse, restarting on contradiction for at most `max_retries` attempts
+// in total.
+pub fn generate_terrain(
+    w: u32,
+    h: u32,
+    tiles: &[TerrainTile],
+    rng: &mut StdRng,
+    max_retries: u32,
+) -> Vec<usize> {
+    for _attempt in 0..max_retries {
+        let mut grid = WfcGrid::new(w, h, tiles.len());
+        let mut contradiction = false;
+        loop {
+            let next = match grid.lowest_entropy_cell(rng) {
+                Some(cell) => cell,
+                None => break,
+            };
+            grid.collapse(next.0, next.1, tiles, rng);
+            if !grid.propagate(next, tiles) {
+                contradiction = true;
+                break;
+            }
+        }
+        if !contradiction {
+            return grid
+                .possibilities
+                .iter()
+                .map(|options| tiles[options[0]].tile_index)
+                .collect();
+        }
+    }
+    // Fall back to a uniform dirt fill if WFC can never settle.
+    vec![1; (w * h) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn generate_terrain_fills_every_cell() {
+        let tiles = default_terrain_tiles();
+        let mut rng = StdRng::seed_from_u64(1);
+        let grid = generate_terrain(6, 6, &tiles, &mut rng, 10);
+        assert_eq!(grid.len(), 36);
+    }
+
+    #[test]
+    fn expand_tile_set_dedupes_symmetric_variants() {
+        // a fully uniform tile looks the same under every transform, so
+        // enabling all the flags should yield exactly one variant
+        let defs = [TileDefinition {
+            tile_index: 0, edges: [0, 0, 0, 0], weight: 1,
+            can_flip: true, can_mirror: true, can_rotate90: true, can_rotate180: true, can_rotate270: true,
+        }];
+        assert_eq!(expand_tile_set(&defs).len(), 1);
+    }
+
+    #[test]
+    fn expand_tile_set_keeps_distinct_rotations() {
+        let defs = [TileDefinition {
+            tile_index: 2, edges: [0, 1, 1, 0], weight: 1,
+            can_flip: false, can_mirror: false, can_rotate90: true, can_rotate180: false, can_rotate270: false,
+        }];
+        // the diagonal tile and its single 90-degree rotation are distinct
+        assert_eq!(expand_tile_set(&defs).len(), 2);
+    }
+}